@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+pub const BACKGROUND_COLOR: &str = "5e81ac";
+pub const SNAKE_COLOR: &str = "a3be8c";
+
+/// Max number of buffered turns a player can queue up between movement ticks.
+pub const INPUT_QUEUE_CAP: usize = 3;
+
+#[derive(Default, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub struct Size {
+    pub width: f32,
+    pub height: f32,
+}
+impl Size {
+    pub fn square(x: f32) -> Self {
+        Self {
+            width: x,
+            height: x,
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+}
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+        }
+    }
+}
+
+pub struct SnakeHead {
+    pub direction: Direction,
+    pub input_queue: VecDeque<Direction>,
+}
+
+pub struct SnakeSegment;
+
+#[derive(Default)]
+pub struct SnakeSegments(pub Vec<Entity>);
+
+#[derive(Default)]
+pub struct LastTailPosition(pub Option<Position>);
+
+#[derive(Default)]
+pub struct Score(pub u32);
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum SnakeMovement {
+    Input,
+    Movement,
+    Eating,
+    Growth,
+}
+
+pub struct Food;
+
+/// Extra score awarded for eating a bonus food on top of the normal growth.
+pub const BONUS_FOOD_SCORE: u32 = 5;
+
+/// Marks a `Food` entity as a time-limited bonus spawned by `FoodSpawnTimer`.
+pub struct BonusFood {
+    pub despawn_timer: Timer,
+}
+
+pub struct GrowthEvent;
+
+pub struct BonusEvent;
+
+pub struct DeathEvent;
+
+pub struct FoodSpawnEvent;
+
+pub struct ScoreText;
+
+pub struct Materials {
+    pub head_material: Handle<ColorMaterial>,
+    pub segment_material: Handle<ColorMaterial>,
+    pub food_material: Handle<ColorMaterial>,
+}
+
+/// Arena dimensions in tiles, read by the movement/spawning/rendering systems.
+pub struct Arena {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Drives the movement tick rate. The interval shrinks as `Score` grows,
+/// down to `min_interval`, making the game speed up over a run.
+pub struct MovementTimer {
+    pub timer: Timer,
+    pub base_step: f32,
+    pub growth_factor: f32,
+    pub min_interval: f32,
+}
+
+impl MovementTimer {
+    pub fn new(base_step: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(base_step, true),
+            base_step,
+            growth_factor: 0.05,
+            min_interval: 0.06,
+        }
+    }
+
+    pub fn interval_for(&self, score: u32) -> f32 {
+        (self.base_step / (1.0 + score as f32 * self.growth_factor)).max(self.min_interval)
+    }
+
+    pub fn reset(&mut self) {
+        self.timer = Timer::from_seconds(self.base_step, true);
+    }
+}
+
+/// Periodically spawns a time-limited bonus `Food` worth extra score.
+pub struct FoodSpawnTimer {
+    pub spawn_timer: Timer,
+    pub interval: f32,
+    pub lifetime: f32,
+}
+
+impl FoodSpawnTimer {
+    pub fn reset(&mut self) {
+        self.spawn_timer = Timer::from_seconds(self.interval, true);
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub enum AppState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+/// All-time best score, loaded from disk on startup and persisted whenever it improves.
+#[derive(Default)]
+pub struct HighScore(pub u32);
+
+/// Freezes the movement/eating/growth tick and bonus food spawning while
+/// `true`, without leaving the `Playing` state (so resuming can't re-trigger
+/// `restart_game`, which only runs on a fresh entry into `Playing`).
+#[derive(Default)]
+pub struct Paused(pub bool);
+
+pub struct MenuUi;
+
+pub struct GameOverUi;
+
+pub struct PausedUi;
@@ -0,0 +1,16 @@
+use std::fs;
+
+const HIGH_SCORE_FILE: &str = "highscore.txt";
+
+/// Reads the persisted high score from disk, defaulting to 0 if the file is
+/// missing or unreadable (e.g. first run).
+pub fn load_high_score() -> u32 {
+    fs::read_to_string(HIGH_SCORE_FILE)
+        .ok()
+        .and_then(|contents| contents.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn save_high_score(score: u32) {
+    let _ = fs::write(HIGH_SCORE_FILE, score.to_string());
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::persistence;
+use crate::systems::*;
+
+/// Embeds the snake game into a Bevy `App`. Owns the arena size, base
+/// movement speed and bonus food cadence so host apps can configure the
+/// board without touching constants.
+pub struct SnakeGamePlugin {
+    pub arena_width: u32,
+    pub arena_height: u32,
+    pub movement_step: f64,
+    pub bonus_food_interval: f32,
+    pub bonus_food_lifetime: f32,
+}
+
+impl Default for SnakeGamePlugin {
+    fn default() -> Self {
+        Self {
+            arena_width: 16,
+            arena_height: 16,
+            movement_step: 0.2,
+            bonus_food_interval: 10.0,
+            bonus_food_lifetime: 5.0,
+        }
+    }
+}
+
+impl Plugin for SnakeGamePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.insert_resource(Arena {
+            width: self.arena_width,
+            height: self.arena_height,
+        })
+            .insert_resource(SnakeSegments::default())
+            .insert_resource(LastTailPosition::default())
+            .insert_resource(Score::default())
+            .insert_resource(HighScore(persistence::load_high_score()))
+            .insert_resource(Paused::default())
+            .insert_resource(MovementTimer::new(self.movement_step as f32))
+            .insert_resource(FoodSpawnTimer {
+                spawn_timer: Timer::from_seconds(self.bonus_food_interval, true),
+                interval: self.bonus_food_interval,
+                lifetime: self.bonus_food_lifetime,
+            })
+            .add_state(AppState::Menu)
+            .add_startup_system(setup.system())
+            .add_system(pause_input.system())
+            .add_system(death_to_gameover.system().after(SnakeMovement::Movement))
+            .add_system(food_event_reader.system().after(SnakeMovement::Eating))
+            .add_system(update_score_text.system().after(SnakeMovement::Growth))
+            .add_system(save_high_score_on_exit.system())
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(menu_setup.system()))
+            .add_system_set(SystemSet::on_update(AppState::Menu).with_system(menu_input.system()))
+            .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(menu_teardown.system()))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Playing).with_system(restart_game.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(playing_unpaused.system())
+                    .with_system(
+                        snake_movement_input
+                            .system()
+                            .label(SnakeMovement::Input)
+                            .before(SnakeMovement::Movement),
+                    )
+                    .with_system(bonus_food_spawner.system())
+                    .with_system(bonus_food_lifetime.system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::GameOver).with_system(gameover_setup.system()),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver).with_system(gameover_input.system()),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::GameOver).with_system(gameover_teardown.system()),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(movement_timer_criteria.system())
+                    .with_system(snake_movement.system().label(SnakeMovement::Movement))
+                    .with_system(
+                        snake_eating
+                            .system()
+                            .label(SnakeMovement::Eating)
+                            .after(SnakeMovement::Movement),
+                    )
+                    .with_system(
+                        snake_growth
+                            .system()
+                            .label(SnakeMovement::Growth)
+                            .after(SnakeMovement::Eating),
+                    )
+                    .with_system(
+                        snake_bonus_growth
+                            .system()
+                            .label(SnakeMovement::Growth)
+                            .after(SnakeMovement::Eating),
+                    ),
+            )
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .with_system(position_translation.system())
+                    .with_system(size_scaling.system()),
+            )
+            .add_event::<GrowthEvent>()
+            .add_event::<BonusEvent>()
+            .add_event::<DeathEvent>()
+            .add_event::<FoodSpawnEvent>();
+    }
+}
@@ -0,0 +1,584 @@
+use bevy::app::AppExit;
+use bevy::ecs::schedule::ShouldRun;
+use bevy::prelude::*;
+use rand::prelude::random;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+use crate::components::*;
+use crate::persistence;
+
+pub fn setup(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands.spawn_bundle(UiCameraBundle::default());
+    let gem_handle = asset_server.load("images/cookie.png");
+    let head_handle = asset_server.load("images/head.png");
+    commands.insert_resource(Materials {
+        head_material: materials.add(head_handle.into()),
+        segment_material: materials.add(Color::hex(SNAKE_COLOR).unwrap().into()),
+        food_material: materials.add(gem_handle.into()),
+    });
+    let font = asset_server.load("fonts/arcade.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::FlexEnd,
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            // Use the `Text::with_section` constructor
+            text: Text::with_section(
+                "0",
+                TextStyle {
+                    font,
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(ScoreText);
+}
+
+pub fn spawn_snake(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    mut segments: ResMut<SnakeSegments>,
+) {
+    segments.0 = vec![
+        commands
+            .spawn_bundle(SpriteBundle {
+                material: materials.head_material.clone(),
+                sprite: Sprite::new(Vec2::new(16.0, 16.0)),
+                transform: Transform::default(),
+                ..Default::default()
+            })
+            .insert(SnakeHead {
+                direction: Direction::Up,
+                input_queue: VecDeque::new(),
+            })
+            .insert(SnakeSegment)
+            .insert(Position { x: 3, y: 3 })
+            .insert(Size::square(1.0))
+            .id(),
+        spawn_segment(
+            commands,
+            &materials.segment_material,
+            Position { x: 3, y: 2 },
+        ),
+    ];
+}
+
+pub fn spawn_segment(
+    mut commands: Commands,
+    material: &Handle<ColorMaterial>,
+    position: Position,
+) -> Entity {
+    commands
+        .spawn_bundle(SpriteBundle {
+            material: material.clone(),
+            ..Default::default()
+        })
+        .insert(SnakeSegment)
+        .insert(position)
+        .insert(Size::square(1.0))
+        .id()
+}
+
+pub fn snake_movement_input(keyboard_input: Res<Input<KeyCode>>, mut heads: Query<&mut SnakeHead>) {
+    if let Some(mut head) = heads.iter_mut().next() {
+        let pressed = if keyboard_input.pressed(KeyCode::A) {
+            Some(Direction::Left)
+        } else if keyboard_input.pressed(KeyCode::D) {
+            Some(Direction::Right)
+        } else if keyboard_input.pressed(KeyCode::S) {
+            Some(Direction::Down)
+        } else if keyboard_input.pressed(KeyCode::W) {
+            Some(Direction::Up)
+        } else {
+            None
+        };
+        if let Some(dir) = pressed {
+            let last_queued = head.input_queue.back().copied().unwrap_or(head.direction);
+            if dir != last_queued
+                && dir != last_queued.opposite()
+                && head.input_queue.len() < INPUT_QUEUE_CAP
+            {
+                head.input_queue.push_back(dir);
+            }
+        }
+    }
+}
+
+pub fn snake_movement(
+    arena: Res<Arena>,
+    segments: ResMut<SnakeSegments>,
+    mut heads: Query<(Entity, &mut Transform, &mut SnakeHead)>,
+    mut positions: Query<&mut Position>,
+    mut last_tail_position: ResMut<LastTailPosition>,
+    mut death_writer: EventWriter<DeathEvent>,
+) {
+    if let Some((head_entity, mut head_transform, mut head)) = heads.iter_mut().next() {
+        let segment_positions = segments
+            .0
+            .iter()
+            .map(|e| *positions.get_mut(*e).unwrap())
+            .collect::<Vec<Position>>();
+        let mut head_pos = positions.get_mut(head_entity).unwrap();
+        let previous_direction = head.direction;
+        let queued_direction = head.input_queue.pop_front().unwrap_or(previous_direction);
+        head.direction = if queued_direction == previous_direction.opposite() {
+            previous_direction
+        } else {
+            queued_direction
+        };
+        match head.direction {
+            Direction::Left => {
+                head_pos.x -= 1;
+                head_transform.rotation = Quat::from_rotation_z((PI / 2.0) as f32)
+            }
+            Direction::Right => {
+                head_pos.x += 1;
+                head_transform.rotation = Quat::from_rotation_z((PI * 1.5) as f32)
+            }
+            Direction::Up => {
+                head_pos.y += 1;
+                head_transform.rotation = Quat::from_rotation_z(0.0)
+            }
+            Direction::Down => {
+                head_pos.y -= 1;
+                head_transform.rotation = Quat::from_rotation_z(PI as f32)
+            }
+        };
+        if head_pos.x < 0
+            || head_pos.y < 0
+            || head_pos.x as u32 >= arena.width
+            || head_pos.y as u32 >= arena.height
+        {
+            death_writer.send(DeathEvent);
+        }
+        if segment_positions.contains(&head_pos) {
+            death_writer.send(DeathEvent);
+        }
+        segment_positions
+            .iter()
+            .zip(segments.0.iter().skip(1))
+            .for_each(|(pos, segment)| {
+                *positions.get_mut(*segment).unwrap() = *pos;
+            });
+        last_tail_position.0 = Some(*segment_positions.last().unwrap());
+    }
+}
+
+/// Runs whenever the snake dies, regardless of which state it happens in.
+pub fn death_to_gameover(
+    mut reader: EventReader<DeathEvent>,
+    mut app_state: ResMut<State<AppState>>,
+    mut movement_timer: ResMut<MovementTimer>,
+) {
+    if reader.iter().next().is_some() {
+        app_state.set(AppState::GameOver).ok();
+        movement_timer.reset();
+    }
+}
+
+/// Resets the board and spawns a fresh snake. Runs on every entry into
+/// `Playing`, both the very first one (from the menu) and restarts after
+/// game over.
+pub fn restart_game(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    segments_res: ResMut<SnakeSegments>,
+    food: Query<Entity, With<Food>>,
+    segments: Query<Entity, With<SnakeSegment>>,
+    mut score: ResMut<Score>,
+    mut food_timer: ResMut<FoodSpawnTimer>,
+    mut food_spawner: EventWriter<FoodSpawnEvent>,
+    mut text_query: Query<&mut Text, With<ScoreText>>,
+) {
+    for ent in food.iter().chain(segments.iter()) {
+        commands.entity(ent).despawn();
+    }
+    spawn_snake(commands, materials, segments_res);
+    score.0 = 0;
+    food_timer.reset();
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = format!("{}", score.0)
+    }
+    food_spawner.send(FoodSpawnEvent);
+}
+
+/// Toggles the `Paused` resource while `Playing`, showing/hiding the pause
+/// overlay directly. This stays off the `AppState` stack on purpose: a
+/// `push`/`pop`'d state re-fires `on_enter(Playing)` on resume, which would
+/// re-trigger `restart_game` and wipe the run every time the game is
+/// unpaused.
+pub fn pause_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    app_state: Res<State<AppState>>,
+    mut paused: ResMut<Paused>,
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+    paused_ui: Query<Entity, With<PausedUi>>,
+) {
+    if *app_state.current() == AppState::Playing && keyboard_input.just_pressed(KeyCode::Space) {
+        paused.0 = !paused.0;
+        if paused.0 {
+            paused_setup(commands, asset_server);
+        } else {
+            paused_teardown(commands, paused_ui);
+        }
+    }
+}
+
+/// Ticks the movement timer at an interval that shrinks as `Score` grows, and
+/// gates the movement/eating/growth system set to the `Playing` state while
+/// unpaused.
+pub fn movement_timer_criteria(
+    time: Res<Time>,
+    score: Res<Score>,
+    app_state: Res<State<AppState>>,
+    paused: Res<Paused>,
+    mut movement_timer: ResMut<MovementTimer>,
+) -> ShouldRun {
+    if *app_state.current() != AppState::Playing || paused.0 {
+        return ShouldRun::No;
+    }
+    let interval = movement_timer.interval_for(score.0);
+    movement_timer
+        .timer
+        .set_duration(std::time::Duration::from_secs_f32(interval));
+    if movement_timer.timer.tick(time.delta()).just_finished() {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Gates the turn-input and bonus food systems to `Playing` while unpaused,
+/// mirroring `movement_timer_criteria` without advancing any timer.
+pub fn playing_unpaused(app_state: Res<State<AppState>>, paused: Res<Paused>) -> ShouldRun {
+    if *app_state.current() == AppState::Playing && !paused.0 {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+pub fn menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/arcade.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Press Enter to start",
+                TextStyle {
+                    font,
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(MenuUi);
+}
+
+pub fn menu_teardown(mut commands: Commands, query: Query<Entity, With<MenuUi>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
+
+pub fn menu_input(keyboard_input: Res<Input<KeyCode>>, mut app_state: ResMut<State<AppState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        app_state.set(AppState::Playing).ok();
+    }
+}
+
+pub fn paused_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/arcade.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                ..Default::default()
+            },
+            text: Text::with_section(
+                "Paused\nPress Space to resume",
+                TextStyle {
+                    font,
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(PausedUi);
+}
+
+pub fn paused_teardown(mut commands: Commands, query: Query<Entity, With<PausedUi>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
+
+pub fn gameover_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    mut high_score: ResMut<HighScore>,
+) {
+    if score.0 > high_score.0 {
+        high_score.0 = score.0;
+        persistence::save_high_score(high_score.0);
+    }
+    let font = asset_server.load("fonts/arcade.ttf");
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                align_self: AlignSelf::Center,
+                ..Default::default()
+            },
+            text: Text::with_section(
+                format!(
+                    "Game over!\nScore: {}\nHigh score: {}\n\nPress Enter to play again",
+                    score.0, high_score.0
+                ),
+                TextStyle {
+                    font,
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..Default::default()
+                },
+            ),
+            ..Default::default()
+        })
+        .insert(GameOverUi);
+}
+
+pub fn gameover_teardown(mut commands: Commands, query: Query<Entity, With<GameOverUi>>) {
+    for ent in query.iter() {
+        commands.entity(ent).despawn();
+    }
+}
+
+pub fn gameover_input(keyboard_input: Res<Input<KeyCode>>, mut app_state: ResMut<State<AppState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        app_state.set(AppState::Playing).ok();
+    }
+}
+
+pub fn save_high_score_on_exit(mut exit_events: EventReader<AppExit>, high_score: Res<HighScore>) {
+    if exit_events.iter().next().is_some() {
+        persistence::save_high_score(high_score.0);
+    }
+}
+
+pub fn snake_eating(
+    mut commands: Commands,
+    mut growth_writer: EventWriter<GrowthEvent>,
+    mut bonus_writer: EventWriter<BonusEvent>,
+    mut food_spawner: EventWriter<FoodSpawnEvent>,
+    food_positions: Query<(Entity, &Position, Option<&BonusFood>), With<Food>>,
+    head_positions: Query<&Position, With<SnakeHead>>,
+) {
+    for head_pos in head_positions.iter() {
+        for (ent, food_pos, bonus) in food_positions.iter() {
+            if food_pos == head_pos {
+                commands.entity(ent).despawn();
+                growth_writer.send(GrowthEvent);
+                if bonus.is_some() {
+                    bonus_writer.send(BonusEvent);
+                } else {
+                    food_spawner.send(FoodSpawnEvent);
+                }
+            }
+        }
+    }
+}
+
+pub fn snake_growth(
+    commands: Commands,
+    last_tail_position: Res<LastTailPosition>,
+    mut segments: ResMut<SnakeSegments>,
+    mut growth_reader: EventReader<GrowthEvent>,
+    mut score: ResMut<Score>,
+    materials: Res<Materials>,
+) {
+    if growth_reader.iter().next().is_some() {
+        segments.0.push(spawn_segment(
+            commands,
+            &materials.segment_material,
+            last_tail_position.0.unwrap(),
+        ));
+        score.0 += 1;
+    }
+}
+
+pub fn snake_bonus_growth(mut bonus_reader: EventReader<BonusEvent>, mut score: ResMut<Score>) {
+    if bonus_reader.iter().next().is_some() {
+        score.0 += BONUS_FOOD_SCORE;
+    }
+}
+
+pub fn food_event_reader(
+    commands: Commands,
+    mut reader: EventReader<FoodSpawnEvent>,
+    materials: Res<Materials>,
+    arena: Res<Arena>,
+    blockers: Query<&Position, Or<(With<Food>, With<SnakeSegment>)>>,
+) {
+    if reader.iter().next().is_some() {
+        food_spawner(commands, materials, arena, blockers)
+    }
+}
+
+fn random_pos(arena: &Arena) -> Position {
+    Position {
+        x: (random::<f32>() * arena.width as f32) as i32,
+        y: (random::<f32>() * arena.height as f32) as i32,
+    }
+}
+
+fn get_empty_pos(arena: &Arena, blockers: Vec<&Position>) -> Position {
+    let mut pos = random_pos(arena);
+    while blockers.iter().any(|x| x == &&pos) {
+        pos = random_pos(arena)
+    }
+    pos
+}
+
+pub fn food_spawner(
+    mut commands: Commands,
+    materials: Res<Materials>,
+    arena: Res<Arena>,
+    blockers: Query<&Position, Or<(With<Food>, With<SnakeSegment>)>>,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(16.0, 16.0)),
+            material: materials.food_material.clone(),
+            transform: Transform::from_rotation(Quat::from_rotation_z(random::<f32>())),
+            ..Default::default()
+        })
+        .insert(Food)
+        .insert(get_empty_pos(&arena, blockers.iter().collect()))
+        .insert(Size::square(1.0));
+}
+
+/// Periodically spawns a second, time-limited bonus food worth extra score,
+/// skipping the spawn while one is already on the board.
+pub fn bonus_food_spawner(
+    time: Res<Time>,
+    mut commands: Commands,
+    materials: Res<Materials>,
+    arena: Res<Arena>,
+    mut food_timer: ResMut<FoodSpawnTimer>,
+    blockers: Query<&Position, Or<(With<Food>, With<SnakeSegment>)>>,
+    bonus_food: Query<Entity, With<BonusFood>>,
+) {
+    if !food_timer.spawn_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if bonus_food.iter().next().is_some() {
+        return;
+    }
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite::new(Vec2::new(16.0, 16.0)),
+            material: materials.food_material.clone(),
+            transform: Transform::from_rotation(Quat::from_rotation_z(random::<f32>())),
+            ..Default::default()
+        })
+        .insert(Food)
+        .insert(BonusFood {
+            despawn_timer: Timer::from_seconds(food_timer.lifetime, false),
+        })
+        .insert(get_empty_pos(&arena, blockers.iter().collect()))
+        .insert(Size::square(1.0));
+}
+
+pub fn bonus_food_lifetime(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut bonus_food: Query<(Entity, &mut BonusFood)>,
+) {
+    for (ent, mut bonus) in bonus_food.iter_mut() {
+        if bonus.despawn_timer.tick(time.delta()).finished() {
+            commands.entity(ent).despawn();
+        }
+    }
+}
+
+pub fn update_score_text(
+    mut query: Query<&mut Text, With<ScoreText>>,
+    score: Res<Score>,
+    mut growth_reader: EventReader<GrowthEvent>,
+    mut bonus_reader: EventReader<BonusEvent>,
+) {
+    if growth_reader.iter().next().is_some() || bonus_reader.iter().next().is_some() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = format!("{}", score.0)
+        }
+    }
+}
+
+pub fn size_scaling(
+    arena: Res<Arena>,
+    windows: Res<Windows>,
+    mut q: Query<(&Size, &mut Sprite)>,
+) {
+    let window = windows.get_primary().unwrap();
+    for (sprite_size, mut sprite) in q.iter_mut() {
+        sprite.size = Vec2::new(
+            sprite_size.width / arena.width as f32 * window.width() as f32,
+            sprite_size.height / arena.height as f32 * window.height() as f32,
+        )
+    }
+}
+
+pub fn position_translation(
+    arena: Res<Arena>,
+    windows: Res<Windows>,
+    mut q: Query<(&Position, &mut Transform)>,
+) {
+    fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
+        let tile_size = bound_window / bound_game;
+        pos / bound_game * bound_window - (bound_window / 2.0) + (tile_size / 2.0)
+    }
+    let window = windows.get_primary().unwrap();
+    for (pos, mut transform) in q.iter_mut() {
+        transform.translation = Vec3::new(
+            convert(pos.x as f32, window.width() as f32, arena.width as f32),
+            convert(pos.y as f32, window.height() as f32, arena.height as f32),
+            0.0,
+        )
+    }
+}